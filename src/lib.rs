@@ -1,167 +1,900 @@
 #![allow(dead_code)]
 
+use std::borrow::Cow;
+use std::fmt;
+
 use indexmap::map::IndexMap;
 use nom::{
     self,
     branch::alt,
-    bytes::complete::{tag, take_until},
-    character::complete::multispace0,
-    combinator::{map, value},
-    multi::separated_list0,
-    number::complete::double,
-    sequence::{delimited, separated_pair},
+    combinator::{consumed, cut, map, map_res, value, verify},
+    error::{context, ContextError, ErrorKind, FromExternalError, ParseError},
+    multi::{fold_many0, separated_list0},
+    sequence::{delimited, preceded, separated_pair},
     IResult,
 };
 
 /// Representation of a node in the json tree
 #[derive(Debug, PartialEq, Clone)]
 enum JsonNode<'a> {
-    Object(Box<IndexMap<&'a str, JsonNode<'a>>>),
+    Object(Box<IndexMap<Cow<'a, str>, JsonNode<'a>>>),
     Array(Box<Vec<JsonNode<'a>>>),
-    String(&'a str),
+    String(Cow<'a, str>),
     Number(f64),
     Boolean(bool),
     Null,
 }
 
+/// The reason a parse failed, independent of where in the input it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum JsonErrorKind {
+    /// The input ended while a token was still expected.
+    UnexpectedEndOfInput,
+    /// An object entry was expected to start with a string key.
+    ExpectedObjectKey,
+    /// A specific punctuation character was expected but not found.
+    ExpectedToken(char),
+    /// The input contains a token that doesn't fit the grammar at this point.
+    UnexpectedToken,
+    /// A `\` inside a string was followed by a character that isn't a valid escape.
+    InvalidEscape,
+    /// A `\uXXXX` escape was malformed, or a surrogate pair was incomplete/invalid.
+    InvalidUnicodeEscape,
+}
+
+/// A parse error, carrying the byte offset (via the remaining input slice) and
+/// an optional human-readable description of what was being parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct JsonError<'a> {
+    input: &'a str,
+    kind: JsonErrorKind,
+    context: Option<&'static str>,
+}
+
+impl<'a> JsonError<'a> {
+    fn new(input: &'a str, kind: JsonErrorKind) -> Self {
+        JsonError {
+            input,
+            kind,
+            context: None,
+        }
+    }
+}
+
+impl<'a> ParseError<&'a str> for JsonError<'a> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        let kind = if input.is_empty() {
+            JsonErrorKind::UnexpectedEndOfInput
+        } else {
+            JsonErrorKind::UnexpectedToken
+        };
+        JsonError::new(input, kind)
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'a> ContextError<&'a str> for JsonError<'a> {
+    fn add_context(_input: &'a str, ctx: &'static str, other: Self) -> Self {
+        match other.context {
+            Some(_) => other,
+            None => JsonError {
+                context: Some(ctx),
+                ..other
+            },
+        }
+    }
+}
+
+impl<'a> FromExternalError<&'a str, std::num::ParseIntError> for JsonError<'a> {
+    fn from_external_error(input: &'a str, _kind: ErrorKind, _error: std::num::ParseIntError) -> Self {
+        JsonError::new(input, JsonErrorKind::InvalidUnicodeEscape)
+    }
+}
+
+/// Shorthand for the `IResult` used throughout this crate's parsers.
+type JResult<'a, O> = IResult<&'a str, O, JsonError<'a>>;
+
+/// Parses `input` as a complete JSON document: surrounding whitespace is
+/// skipped, and the whole of `input` must be consumed by a single value.
+/// Nom's internal error is mapped into a `JsonError` along the way.
+fn parse(input: &str) -> Result<JsonNode<'_>, JsonError<'_>> {
+    parse_document::<Complete, Lenient>(input)
+}
+
+/// Like [`parse`], but in strict RFC 8259 conformance mode: rejects
+/// leading-dot numbers (`.14`), a leading `+` on numbers, and unescaped
+/// control characters inside strings. Trailing commas in objects and arrays
+/// are rejected in both modes.
+fn parse_strict(input: &str) -> Result<JsonNode<'_>, JsonError<'_>> {
+    parse_document::<Complete, Strict>(input)
+}
+
+/// Shared implementation behind [`parse`] and [`parse_strict`]: skips
+/// leading/trailing whitespace, requires the whole input to be consumed by a
+/// single value, and maps nom's internal error into a `JsonError`.
+fn parse_document<M: Mode, S: Strictness>(input: &str) -> Result<JsonNode<'_>, JsonError<'_>> {
+    match delimited(M::multispace0, parse_json::<M, S>, M::multispace0)(input) {
+        Ok(("", node)) => Ok(node),
+        Ok((rest, _)) => Err(JsonError {
+            input: rest,
+            kind: JsonErrorKind::UnexpectedToken,
+            context: Some("trailing data after the end of the document"),
+        }),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(e),
+        Err(nom::Err::Incomplete(_)) => {
+            Err(JsonError::new(input, JsonErrorKind::UnexpectedEndOfInput))
+        }
+    }
+}
+
+/// The primitive parsers the grammar is built out of, so that the same
+/// recursive grammar below can run either against a fully-buffered input
+/// ([`Complete`], which turns "not enough data" into a hard parse error) or
+/// against a partial buffer that may still be growing ([`Streaming`], which
+/// turns it into `nom::Err::Incomplete` so the caller can feed more bytes).
+trait Mode {
+    fn tag<'a>(input: &'a str, literal: &'static str) -> JResult<'a, &'a str>;
+    fn char(input: &str, expected: char) -> JResult<'_, char>;
+    fn multispace0(input: &str) -> JResult<'_, &str>;
+    fn double(input: &str) -> JResult<'_, f64>;
+    fn hex_digits(input: &str) -> JResult<'_, &str>;
+    fn take_till1<F: Fn(char) -> bool + Copy>(input: &str, stop: F) -> JResult<'_, &str>;
+}
+
+/// Grammar over a fully-buffered `&str`: the mode [`parse`] and
+/// [`parse_strict`] use.
+enum Complete {}
+
+impl Mode for Complete {
+    fn tag<'a>(input: &'a str, literal: &'static str) -> JResult<'a, &'a str> {
+        nom::bytes::complete::tag(literal)(input)
+    }
+
+    fn char(input: &str, expected: char) -> JResult<'_, char> {
+        nom::character::complete::char(expected)(input)
+    }
+
+    fn multispace0(input: &str) -> JResult<'_, &str> {
+        nom::character::complete::multispace0(input)
+    }
+
+    fn double(input: &str) -> JResult<'_, f64> {
+        nom::number::complete::double(input)
+    }
+
+    fn hex_digits(input: &str) -> JResult<'_, &str> {
+        nom::bytes::complete::take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit())(input)
+    }
+
+    fn take_till1<F: Fn(char) -> bool + Copy>(input: &str, stop: F) -> JResult<'_, &str> {
+        nom::bytes::complete::take_till1(stop)(input)
+    }
+}
+
+/// Grammar over a buffer that may still be growing: running out of bytes on
+/// an incomplete token yields `nom::Err::Incomplete` instead of a hard error.
+enum Streaming {}
+
+impl Mode for Streaming {
+    fn tag<'a>(input: &'a str, literal: &'static str) -> JResult<'a, &'a str> {
+        nom::bytes::streaming::tag(literal)(input)
+    }
+
+    fn char(input: &str, expected: char) -> JResult<'_, char> {
+        nom::character::streaming::char(expected)(input)
+    }
+
+    fn multispace0(input: &str) -> JResult<'_, &str> {
+        nom::character::streaming::multispace0(input)
+    }
+
+    fn double(input: &str) -> JResult<'_, f64> {
+        nom::number::streaming::double(input)
+    }
+
+    fn hex_digits(input: &str) -> JResult<'_, &str> {
+        nom::bytes::streaming::take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit())(input)
+    }
+
+    fn take_till1<F: Fn(char) -> bool + Copy>(input: &str, stop: F) -> JResult<'_, &str> {
+        nom::bytes::streaming::take_till1(stop)(input)
+    }
+}
+
+/// Distinguishes the lenient grammar this crate accepts by default from an
+/// opt-in mode that rejects JSON extensions other parsers quietly allow, so
+/// the crate can also claim RFC 8259 conformance.
+trait Strictness {
+    /// Whether a number literal is accepted, given both `raw` (the exact text
+    /// it matched) and `value` (what it parses to). Used to reject extensions
+    /// like a leading `.` or a leading `+`, and non-finite values produced by
+    /// an exponent large enough to overflow to infinity.
+    fn accepts_number(raw: &str, value: f64) -> bool;
+
+    /// Whether `c` ends a run of literal (non-escaped) string characters.
+    fn is_string_literal_stop_char(c: char) -> bool;
+}
+
+/// The grammar this crate has always accepted: leading-dot numbers, a
+/// leading `+`, literal control characters inside strings, and numbers whose
+/// exponent overflows to infinity are all fine.
+enum Lenient {}
+
+impl Strictness for Lenient {
+    fn accepts_number(_raw: &str, _value: f64) -> bool {
+        true
+    }
+
+    fn is_string_literal_stop_char(c: char) -> bool {
+        c == '"' || c == '\\'
+    }
+}
+
+/// RFC 8259 conformance: rejects the extensions [`Lenient`] accepts.
+enum Strict {}
+
+impl Strictness for Strict {
+    fn accepts_number(raw: &str, value: f64) -> bool {
+        let unsigned = raw.trim_start_matches('-');
+        !unsigned.starts_with('.') && !unsigned.starts_with('+') && value.is_finite()
+    }
+
+    fn is_string_literal_stop_char(c: char) -> bool {
+        // RFC 8259 only forbids unescaped U+0000-U+001F, unlike
+        // `char::is_control`, which also covers U+007F and U+0080-U+009F.
+        // Matches the set `write_escaped_string` escapes, so parse and
+        // serialize agree.
+        c == '"' || c == '\\' || (c as u32) < 0x20
+    }
+}
+
+/// Matches a single punctuation character, reporting `ExpectedToken(expected)`
+/// on failure instead of a generic nom error.
+fn expect_char<M: Mode>(expected: char) -> impl Fn(&str) -> JResult<'_, char> {
+    move |input: &str| {
+        M::char(input, expected).map_err(|e| {
+            e.map(|err| JsonError {
+                kind: JsonErrorKind::ExpectedToken(expected),
+                ..err
+            })
+        })
+    }
+}
+
 /// Parsing a full json will result in a JsonNode and should consume all the input
-fn parse_json(json: &str) -> IResult<&str, JsonNode> {
+fn parse_json<M: Mode, S: Strictness>(json: &str) -> JResult<'_, JsonNode<'_>> {
     alt((
-        parse_object,
-        parse_array,
-        parse_number,
-        parse_string,
-        parse_boolean,
-        parse_null,
+        parse_object::<M, S>,
+        parse_array::<M, S>,
+        parse_number::<M, S>,
+        parse_string::<M, S>,
+        parse_boolean::<M>,
+        parse_null::<M>,
     ))(json)
 }
 
-fn parse_object(json: &str) -> IResult<&str, JsonNode> {
-    map(
-        // An object is delimited by {}
-        delimited(
-            tag("{"),
-            // and contains a list of entries separated by comma (','), optionally empty
-            separated_list0(
-                tag(","),
-                // each entry is made of two parts: key and value, separated by colon (':')
-                separated_pair(
-                    delimited(multispace0, parse_string_inner, multispace0),
-                    tag(":"),
-                    delimited(multispace0, parse_json, multispace0),
+/// An object entry's key, which must be a string; failures here are reported
+/// as `ExpectedObjectKey` rather than a generic string-parsing error.
+fn parse_object_key<M: Mode, S: Strictness>(json: &str) -> JResult<'_, Cow<'_, str>> {
+    delimited(M::multispace0, parse_string_inner::<M, S>, M::multispace0)(json).map_err(|e| {
+        e.map(|err| JsonError::new(err.input, JsonErrorKind::ExpectedObjectKey))
+    })
+}
+
+fn parse_object<M: Mode, S: Strictness>(json: &str) -> JResult<'_, JsonNode<'_>> {
+    context(
+        "object",
+        map(
+            // An object is delimited by {}
+            delimited(
+                expect_char::<M>('{'),
+                // and contains a list of entries separated by comma (','), optionally empty
+                separated_list0(
+                    expect_char::<M>(','),
+                    // each entry is made of two parts: key and value, separated by colon (':')
+                    separated_pair(
+                        parse_object_key::<M, S>,
+                        expect_char::<M>(':'),
+                        delimited(M::multispace0, parse_json::<M, S>, M::multispace0),
+                    ),
                 ),
+                expect_char::<M>('}'),
             ),
-            tag("}"),
+            |v| JsonNode::Object(Box::new(v.into_iter().collect())),
         ),
-        |v| JsonNode::Object(Box::new(v.into_iter().collect())),
     )(json)
 }
 
-fn parse_array(json: &str) -> IResult<&str, JsonNode> {
-    map(
-        // An array is delimited by []
-        delimited(
-            tag("["),
-            // and contains a list of entries separated by comma (','), optionally empty
-            separated_list0(delimited(multispace0, tag(","), multispace0), parse_json),
-            tag("]"),
+fn parse_array<M: Mode, S: Strictness>(json: &str) -> JResult<'_, JsonNode<'_>> {
+    context(
+        "array",
+        map(
+            // An array is delimited by []
+            delimited(
+                expect_char::<M>('['),
+                // and contains a list of entries separated by comma (','), optionally empty
+                separated_list0(
+                    delimited(M::multispace0, expect_char::<M>(','), M::multispace0),
+                    parse_json::<M, S>,
+                ),
+                expect_char::<M>(']'),
+            ),
+            |v| JsonNode::Array(Box::new(v)),
         ),
-        |v| JsonNode::Array(Box::new(v)),
     )(json)
 }
 
-fn parse_number(json: &str) -> IResult<&str, JsonNode> {
-    // We can reuse the parser already built into nom!
-    map(double, |n| JsonNode::Number(n))(json)
+/// Parses a number. In [`Strict`] mode, rejects the leading-dot (`.14`) and
+/// leading-`+` extensions that nom's `double` otherwise happily accepts, as
+/// well as an exponent large enough to overflow to a non-finite value.
+fn parse_number<M: Mode, S: Strictness>(json: &str) -> JResult<'_, JsonNode<'_>> {
+    map(
+        verify(consumed(M::double), |(raw, value): &(&str, f64)| {
+            S::accepts_number(raw, *value)
+        }),
+        |(_, value)| JsonNode::Number(value),
+    )(json)
+}
+
+/// One fragment of a string's contents: either a run of unescaped characters,
+/// or a single character produced by an escape sequence.
+enum StringFragment<'a> {
+    Literal(&'a str),
+    EscapedChar(char),
 }
 
-/// Parses a string and returns it "raw", without building a JsonNode
-fn parse_string_inner(json: &str) -> IResult<&str, &str> {
-    // A string is delimited by quote marks. Here we do not handle unicode or escape characters,
-    // but take a lookt at https://github.com/rust-bakery/nom/blob/main/examples/string.rs
-    delimited(tag("\""), take_until("\""), tag("\""))(json)
+/// Parses the 4 hex digits of a `\uXXXX` escape into the code unit they encode.
+fn parse_unicode_code_unit<M: Mode>(json: &str) -> JResult<'_, u16> {
+    map_res(M::hex_digits, |hex| u16::from_str_radix(hex, 16))(json)
+}
+
+/// Parses a `\uXXXX` escape, combining surrogate pairs into a single `char`.
+/// A lone high surrogate must be followed by a `\u` low surrogate, and a lone
+/// low surrogate (without a preceding high surrogate) is rejected.
+fn parse_unicode_escape<M: Mode>(json: &str) -> JResult<'_, char> {
+    let (rest, high) = preceded(|i| M::char(i, 'u'), parse_unicode_code_unit::<M>)(json)?;
+
+    if (0xD800..0xDC00).contains(&high) {
+        // High surrogate: it must be immediately followed by a low surrogate.
+        let (rest, low) = preceded(
+            |i| M::tag(i, "\\u"),
+            verify(parse_unicode_code_unit::<M>, |low| {
+                (0xDC00..0xE000).contains(low)
+            }),
+        )(rest)
+        .map_err(|e| {
+            e.map(|err| JsonError {
+                kind: JsonErrorKind::InvalidUnicodeEscape,
+                ..err
+            })
+        })?;
+        let code_point = 0x10000 + ((high as u32 - 0xD800) << 10) + (low as u32 - 0xDC00);
+        let c = char::from_u32(code_point)
+            .ok_or_else(|| nom::Err::Error(JsonError::new(rest, JsonErrorKind::InvalidUnicodeEscape)))?;
+        Ok((rest, c))
+    } else if (0xDC00..0xE000).contains(&high) {
+        // A low surrogate can only appear after a high surrogate, never on its own.
+        Err(nom::Err::Error(JsonError::new(
+            json,
+            JsonErrorKind::InvalidUnicodeEscape,
+        )))
+    } else {
+        let c = char::from_u32(high as u32)
+            .ok_or_else(|| nom::Err::Error(JsonError::new(rest, JsonErrorKind::InvalidUnicodeEscape)))?;
+        Ok((rest, c))
+    }
+}
+
+/// Parses a two-character escape (`\"`, `\\`, `\/`, `\b`, `\f`, `\n`, `\r`, `\t`) or
+/// a `\uXXXX` unicode escape (including surrogate pairs), returning the char it represents.
+///
+/// Once the leading `\` is consumed, a valid escape is mandatory: the
+/// alternatives are wrapped in `cut` so a malformed escape or surrogate pair
+/// becomes a `Failure` instead of a backtrackable `Error`. Otherwise the
+/// surrounding `fold_many0` (in `parse_string_inner`) would treat the failure
+/// as "no more fragments" and the string would fail later, misleadingly, at
+/// the closing-quote check.
+fn parse_escaped_char<M: Mode>(json: &str) -> JResult<'_, char> {
+    let (rest, _) = M::char(json, '\\')?;
+    cut(alt((
+        value('"', |i| M::char(i, '"')),
+        value('\\', |i| M::char(i, '\\')),
+        value('/', |i| M::char(i, '/')),
+        value('\u{0008}', |i| M::char(i, 'b')),
+        value('\u{000C}', |i| M::char(i, 'f')),
+        value('\n', |i| M::char(i, 'n')),
+        value('\r', |i| M::char(i, 'r')),
+        value('\t', |i| M::char(i, 't')),
+        parse_unicode_escape::<M>,
+    )))(rest)
+    .map_err(|e| {
+        e.map(|err| match err.kind {
+            // parse_unicode_escape already picked a more specific kind; keep it.
+            JsonErrorKind::InvalidUnicodeEscape => err,
+            _ => JsonError {
+                kind: JsonErrorKind::InvalidEscape,
+                ..err
+            },
+        })
+    })
+}
+
+/// Parses one fragment of a string's contents. In [`Strict`] mode, a literal
+/// run stops at (and thus rejects, once the fragment loop tries to close the
+/// string) an unescaped control character.
+fn parse_string_fragment<M: Mode, S: Strictness>(json: &str) -> JResult<'_, StringFragment<'_>> {
+    alt((
+        map(
+            |i| M::take_till1(i, S::is_string_literal_stop_char),
+            StringFragment::Literal,
+        ),
+        map(parse_escaped_char::<M>, StringFragment::EscapedChar),
+    ))(json)
+}
+
+/// Parses a string and returns it "raw", without building a JsonNode. Strings
+/// without any escapes stay borrowed from the input; as soon as an escape is
+/// found we switch to building an owned `String`.
+fn parse_string_inner<M: Mode, S: Strictness>(json: &str) -> JResult<'_, Cow<'_, str>> {
+    context(
+        "string",
+        delimited(
+            expect_char::<M>('"'),
+            fold_many0(
+                parse_string_fragment::<M, S>,
+                || None,
+                |acc: Option<Cow<str>>, fragment| {
+                    Some(match (acc, fragment) {
+                        (None, StringFragment::Literal(s)) => Cow::Borrowed(s),
+                        (None, StringFragment::EscapedChar(c)) => Cow::Owned(c.to_string()),
+                        (Some(mut owned), StringFragment::Literal(s)) => {
+                            owned.to_mut().push_str(s);
+                            owned
+                        }
+                        (Some(mut owned), StringFragment::EscapedChar(c)) => {
+                            owned.to_mut().push(c);
+                            owned
+                        }
+                    })
+                },
+            ),
+            expect_char::<M>('"'),
+        ),
+    )(json)
+    .map(|(rest, s)| (rest, s.unwrap_or(Cow::Borrowed(""))))
 }
 
 /// Parses a string and wraps it into a JsonNode
-fn parse_string(json: &str) -> IResult<&str, JsonNode> {
-    map(parse_string_inner, |s: &str| JsonNode::String(s))(json)
+fn parse_string<M: Mode, S: Strictness>(json: &str) -> JResult<'_, JsonNode<'_>> {
+    map(parse_string_inner::<M, S>, JsonNode::String)(json)
 }
 
-fn parse_boolean(json: &str) -> IResult<&str, JsonNode> {
+fn parse_boolean<M: Mode>(json: &str) -> JResult<'_, JsonNode<'_>> {
     // A boolean is the literal true or false
     alt((
-        value(JsonNode::Boolean(true), tag("true")),
-        value(JsonNode::Boolean(false), tag("false")),
+        value(JsonNode::Boolean(true), |i| M::tag(i, "true")),
+        value(JsonNode::Boolean(false), |i| M::tag(i, "false")),
     ))(json)
 }
 
-fn parse_null(json: &str) -> IResult<&str, JsonNode> {
+fn parse_null<M: Mode>(json: &str) -> JResult<'_, JsonNode<'_>> {
     // Simplest case: a literal value
-    value(JsonNode::Null, tag("null"))(json)
+    value(JsonNode::Null, |i| M::tag(i, "null"))(json)
+}
+
+/// Parses `json` as a single value against a partial buffer: if a token is
+/// cut off at the end of `json`, this returns `nom::Err::Incomplete` instead
+/// of a hard parse error, so the caller can append more bytes and retry.
+/// Lenient, like [`parse`].
+fn parse_streaming(json: &str) -> JResult<'_, JsonNode<'_>> {
+    parse_json::<Streaming, Lenient>(json)
+}
+
+impl<'a> JsonNode<'a> {
+    /// Serializes this node to a compact JSON string, with no extra whitespace.
+    fn to_json_string(&self) -> String {
+        let mut out = String::new();
+        write_node(self, &mut out, None, 0);
+        out
+    }
+
+    /// Serializes this node to a JSON string pretty-printed with `indent_width`
+    /// spaces per nesting level.
+    fn to_json_string_pretty(&self, indent_width: usize) -> String {
+        let mut out = String::new();
+        write_node(self, &mut out, Some(indent_width), 0);
+        out
+    }
+
+    /// Returns the string slice if this node is a `String`, `None` otherwise.
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonNode::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the number if this node is a `Number`, `None` otherwise.
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonNode::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean if this node is a `Boolean`, `None` otherwise.
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonNode::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if this node is an `Array`, `None` otherwise.
+    fn as_array(&self) -> Option<&Vec<JsonNode<'a>>> {
+        match self {
+            JsonNode::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if this node is an `Object`, `None` otherwise.
+    fn as_object(&self) -> Option<&IndexMap<Cow<'a, str>, JsonNode<'a>>> {
+        match self {
+            JsonNode::Object(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this node is `Null`.
+    fn is_null(&self) -> bool {
+        matches!(self, JsonNode::Null)
+    }
+
+    /// If this node is an `Object`, looks up `key` in it.
+    fn get(&self, key: &str) -> Option<&JsonNode<'a>> {
+        self.as_object().and_then(|entries| entries.get(key))
+    }
+
+    /// If this node is an `Array`, looks up the element at `index`.
+    fn get_index(&self, index: usize) -> Option<&JsonNode<'a>> {
+        self.as_array().and_then(|items| items.get(index))
+    }
+}
+
+impl<'a> From<&'a str> for JsonNode<'a> {
+    fn from(s: &'a str) -> Self {
+        JsonNode::String(Cow::Borrowed(s))
+    }
+}
+
+impl<'a> From<String> for JsonNode<'a> {
+    fn from(s: String) -> Self {
+        JsonNode::String(Cow::Owned(s))
+    }
+}
+
+impl<'a> From<f64> for JsonNode<'a> {
+    fn from(n: f64) -> Self {
+        JsonNode::Number(n)
+    }
+}
+
+impl<'a> From<bool> for JsonNode<'a> {
+    fn from(b: bool) -> Self {
+        JsonNode::Boolean(b)
+    }
+}
+
+impl<'a> From<Vec<JsonNode<'a>>> for JsonNode<'a> {
+    fn from(items: Vec<JsonNode<'a>>) -> Self {
+        JsonNode::Array(Box::new(items))
+    }
+}
+
+impl<'a> From<IndexMap<Cow<'a, str>, JsonNode<'a>>> for JsonNode<'a> {
+    fn from(entries: IndexMap<Cow<'a, str>, JsonNode<'a>>) -> Self {
+        JsonNode::Object(Box::new(entries))
+    }
+}
+
+/// Extracts the owned string out of a `JsonNode::String`, failing with the
+/// original node if it held a different variant.
+impl<'a> TryFrom<JsonNode<'a>> for String {
+    type Error = JsonNode<'a>;
+
+    fn try_from(node: JsonNode<'a>) -> Result<Self, Self::Error> {
+        match node {
+            JsonNode::String(s) => Ok(s.into_owned()),
+            other => Err(other),
+        }
+    }
+}
+
+impl<'a> TryFrom<JsonNode<'a>> for f64 {
+    type Error = JsonNode<'a>;
+
+    fn try_from(node: JsonNode<'a>) -> Result<Self, Self::Error> {
+        match node {
+            JsonNode::Number(n) => Ok(n),
+            other => Err(other),
+        }
+    }
+}
+
+impl<'a> TryFrom<JsonNode<'a>> for bool {
+    type Error = JsonNode<'a>;
+
+    fn try_from(node: JsonNode<'a>) -> Result<Self, Self::Error> {
+        match node {
+            JsonNode::Boolean(b) => Ok(b),
+            other => Err(other),
+        }
+    }
+}
+
+impl<'a> TryFrom<JsonNode<'a>> for Vec<JsonNode<'a>> {
+    type Error = JsonNode<'a>;
+
+    fn try_from(node: JsonNode<'a>) -> Result<Self, Self::Error> {
+        match node {
+            JsonNode::Array(items) => Ok(*items),
+            other => Err(other),
+        }
+    }
+}
+
+impl<'a> TryFrom<JsonNode<'a>> for IndexMap<Cow<'a, str>, JsonNode<'a>> {
+    type Error = JsonNode<'a>;
+
+    fn try_from(node: JsonNode<'a>) -> Result<Self, Self::Error> {
+        match node {
+            JsonNode::Object(entries) => Ok(*entries),
+            other => Err(other),
+        }
+    }
+}
+
+impl<'a> fmt::Display for JsonNode<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_json_string())
+    }
+}
+
+/// Writes `node` as JSON into `out`. When `indent` is `Some(width)`, objects and
+/// arrays are broken across lines and indented by `width` spaces per `depth`;
+/// when it's `None` the output is compact, with no extra whitespace.
+fn write_node(node: &JsonNode, out: &mut String, indent: Option<usize>, depth: usize) {
+    match node {
+        JsonNode::Null => out.push_str("null"),
+        JsonNode::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        // NaN and +/-Infinity have no JSON representation; serialize them as
+        // `null` rather than emitting invalid JSON like `NaN` or `inf`.
+        JsonNode::Number(n) if n.is_finite() => out.push_str(&n.to_string()),
+        JsonNode::Number(_) => out.push_str("null"),
+        JsonNode::String(s) => write_escaped_string(s, out),
+        JsonNode::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_newline_and_indent(out, indent, depth + 1);
+                write_node(item, out, indent, depth + 1);
+            }
+            if !items.is_empty() {
+                write_newline_and_indent(out, indent, depth);
+            }
+            out.push(']');
+        }
+        JsonNode::Object(entries) => {
+            out.push('{');
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_newline_and_indent(out, indent, depth + 1);
+                write_escaped_string(key, out);
+                out.push(':');
+                if indent.is_some() {
+                    out.push(' ');
+                }
+                write_node(value, out, indent, depth + 1);
+            }
+            if !entries.is_empty() {
+                write_newline_and_indent(out, indent, depth);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_newline_and_indent(out: &mut String, indent: Option<usize>, depth: usize) {
+    if let Some(width) = indent {
+        out.push('\n');
+        out.push_str(&" ".repeat(width * depth));
+    }
+}
+
+/// Writes `s` as a quoted JSON string, re-escaping quotes, backslashes and
+/// control characters (as `\uXXXX`, except for the usual two-char escapes).
+fn write_escaped_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{0008}' => out.push_str("\\b"),
+            '\u{000C}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
 }
 
 #[cfg(test)]
 mod tests {
     use indexmap::IndexMap;
-    use nom::error::make_error;
 
     use crate::{
-        parse_array, parse_boolean, parse_null, parse_number, parse_object, parse_string, JsonNode,
+        parse, parse_array, parse_boolean, parse_json, parse_null, parse_number, parse_object,
+        parse_object_key, parse_strict, parse_streaming, parse_string, Complete, JsonError,
+        JsonErrorKind, JsonNode, Lenient,
     };
 
     #[test]
     fn can_parse_null() {
-        assert_eq!(Ok(("", JsonNode::Null)), parse_null("null"));
+        assert_eq!(Ok(("", JsonNode::Null)), parse_null::<Complete>("null"));
 
         assert_eq!(
-            parse_null("something"),
-            Err(nom::Err::Error(make_error(
+            parse_null::<Complete>("something"),
+            Err(nom::Err::Error(JsonError::new(
                 "something",
-                nom::error::ErrorKind::Tag
+                JsonErrorKind::UnexpectedToken
             )))
         );
     }
 
     #[test]
     fn can_parse_boolean() {
-        assert_eq!(Ok(("", JsonNode::Boolean(true))), parse_boolean("true"));
-        assert_eq!(Ok(("", JsonNode::Boolean(false))), parse_boolean("false"));
+        assert_eq!(Ok(("", JsonNode::Boolean(true))), parse_boolean::<Complete>("true"));
+        assert_eq!(Ok(("", JsonNode::Boolean(false))), parse_boolean::<Complete>("false"));
 
         assert_eq!(
-            parse_boolean("something"),
-            Err(nom::Err::Error(make_error(
+            parse_boolean::<Complete>("something"),
+            Err(nom::Err::Error(JsonError::new(
                 "something",
-                nom::error::ErrorKind::Tag
+                JsonErrorKind::UnexpectedToken
             )))
         );
     }
 
     #[test]
     fn can_parse_string() {
-        assert_eq!(Ok(("", JsonNode::String("abc"))), parse_string("\"abc\""));
+        assert_eq!(
+            Ok(("", JsonNode::String("abc".into()))),
+            parse_string::<Complete, Lenient>("\"abc\"")
+        );
 
         assert_eq!(
-            parse_string("something"),
-            Err(nom::Err::Error(make_error(
-                "something",
-                nom::error::ErrorKind::Tag
-            )))
+            parse_string::<Complete, Lenient>("something"),
+            Err(nom::Err::Error(JsonError {
+                input: "something",
+                kind: JsonErrorKind::ExpectedToken('"'),
+                context: Some("string"),
+            }))
+        );
+    }
+
+    #[test]
+    fn can_parse_string_with_escapes() {
+        assert_eq!(
+            Ok(("", JsonNode::String("a\"b".into()))),
+            parse_string::<Complete, Lenient>("\"a\\\"b\"")
+        );
+        assert_eq!(Ok(("", JsonNode::String("\n".into()))), parse_string::<Complete, Lenient>("\"\\n\""));
+        assert_eq!(
+            Ok(("", JsonNode::String("\\".into()))),
+            parse_string::<Complete, Lenient>("\"\\\\\"")
+        );
+    }
+
+    #[test]
+    fn can_parse_string_with_unicode_escapes() {
+        assert_eq!(
+            Ok(("", JsonNode::String("\u{e9}".into()))),
+            parse_string::<Complete, Lenient>("\"\\u00e9\"")
+        );
+
+        // Surrogate pair for U+1F600 (grinning face emoji)
+        assert_eq!(
+            Ok(("", JsonNode::String("\u{1F600}".into()))),
+            parse_string::<Complete, Lenient>("\"\\ud83d\\ude00\"")
+        );
+    }
+
+    #[test]
+    fn rejects_lone_surrogates() {
+        // High surrogate with no low surrogate following: once we commit to a
+        // `\uXXXX` escape, a malformed surrogate pair is a fatal `Failure`,
+        // not a backtrackable `Error`.
+        assert_eq!(
+            parse_string::<Complete, Lenient>("\"\\ud83d\"").unwrap_err(),
+            nom::Err::Failure(JsonError {
+                input: "\"",
+                kind: JsonErrorKind::InvalidUnicodeEscape,
+                context: Some("string"),
+            })
+        );
+        // Low surrogate with no high surrogate before it: rejected immediately.
+        assert_eq!(
+            parse_string::<Complete, Lenient>("\"\\ude00\"").unwrap_err(),
+            nom::Err::Failure(JsonError {
+                input: "ude00\"",
+                kind: JsonErrorKind::InvalidUnicodeEscape,
+                context: Some("string"),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_escapes() {
+        assert_eq!(
+            parse_string::<Complete, Lenient>("\"\\q\"").unwrap_err(),
+            nom::Err::Failure(JsonError {
+                input: "q\"",
+                kind: JsonErrorKind::InvalidEscape,
+                context: Some("string"),
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_escapes_are_reported_through_top_level_parse() {
+        // Regression test: before escapes were wrapped in `cut`, a bad escape
+        // deep inside a larger document backtracked out of the string
+        // entirely and was reported as a generic `ExpectedToken` for the
+        // enclosing array's `]` or object's `}`, rather than the actual
+        // `InvalidEscape` / `InvalidUnicodeEscape` failure.
+        assert_eq!(
+            parse("[1, \"\\q\"]"),
+            Err(JsonError {
+                input: "q\"]",
+                kind: JsonErrorKind::InvalidEscape,
+                context: Some("string"),
+            })
+        );
+        assert_eq!(
+            parse("{\"k\": \"\\ud83d\"}"),
+            Err(JsonError {
+                input: "\"}",
+                kind: JsonErrorKind::InvalidUnicodeEscape,
+                context: Some("string"),
+            })
         );
     }
 
     #[test]
     fn can_parse_numbers() {
-        assert_eq!(Ok(("", JsonNode::Number(42f64))), parse_number("42"));
-        assert_eq!(Ok(("", JsonNode::Number(1.2f64))), parse_number("1.2"));
-        assert_eq!(Ok(("", JsonNode::Number(1.3e4f64))), parse_number("1.3e4"));
-        assert_eq!(Ok(("", JsonNode::Number(0.14f64))), parse_number(".14"));
+        assert_eq!(Ok(("", JsonNode::Number(42f64))), parse_number::<Complete, Lenient>("42"));
+        assert_eq!(Ok(("", JsonNode::Number(1.2f64))), parse_number::<Complete, Lenient>("1.2"));
+        assert_eq!(Ok(("", JsonNode::Number(1.3e4f64))), parse_number::<Complete, Lenient>("1.3e4"));
+        assert_eq!(Ok(("", JsonNode::Number(0.14f64))), parse_number::<Complete, Lenient>(".14"));
 
         assert_eq!(
-            parse_string("something"),
-            Err(nom::Err::Error(make_error(
-                "something",
-                nom::error::ErrorKind::Tag
-            )))
+            parse_string::<Complete, Lenient>("something"),
+            Err(nom::Err::Error(JsonError {
+                input: "something",
+                kind: JsonErrorKind::ExpectedToken('"'),
+                context: Some("string"),
+            }))
         );
     }
 
@@ -169,11 +902,14 @@ mod tests {
     fn can_parse_array() {
         assert_eq!(
             Ok(("", JsonNode::Array(Box::new(Vec::new())))),
-            parse_array("[]")
+            parse_array::<Complete, Lenient>("[]")
         );
         assert_eq!(
-            Ok(("", JsonNode::Array(Box::new(vec![JsonNode::Boolean(true)])))),
-            parse_array("[true]")
+            Ok((
+                "",
+                JsonNode::Array(Box::new(vec![JsonNode::Boolean(true)]))
+            )),
+            parse_array::<Complete, Lenient>("[true]")
         );
         assert_eq!(
             Ok((
@@ -184,15 +920,16 @@ mod tests {
                     JsonNode::Boolean(false)
                 ]))
             )),
-            parse_array("[false, null, false]")
+            parse_array::<Complete, Lenient>("[false, null, false]")
         );
 
         assert_eq!(
-            parse_array("something"),
-            Err(nom::Err::Error(make_error(
-                "something",
-                nom::error::ErrorKind::Tag
-            )))
+            parse_array::<Complete, Lenient>("something"),
+            Err(nom::Err::Error(JsonError {
+                input: "something",
+                kind: JsonErrorKind::ExpectedToken('['),
+                context: Some("array"),
+            }))
         );
     }
 
@@ -200,35 +937,269 @@ mod tests {
     fn can_parse_objects() {
         assert_eq!(
             Ok(("", JsonNode::Object(Box::new(IndexMap::new())))),
-            parse_object("{}")
+            parse_object::<Complete, Lenient>("{}")
         );
         assert_eq!(
             Ok((
                 "",
                 JsonNode::Object(Box::new(
-                    vec![("b", JsonNode::Boolean(false))].into_iter().collect()
+                    vec![("b".into(), JsonNode::Boolean(false))]
+                        .into_iter()
+                        .collect()
                 ))
             )),
-            parse_object("{\"b\": false}")
+            parse_object::<Complete, Lenient>("{\"b\": false}")
         );
         assert_eq!(
             Ok((
                 "",
                 JsonNode::Object(Box::new(
-                    vec![("a", JsonNode::String("x")), ("b", JsonNode::Boolean(true)),]
-                        .into_iter()
-                        .collect()
+                    vec![
+                        ("a".into(), JsonNode::String("x".into())),
+                        ("b".into(), JsonNode::Boolean(true)),
+                    ]
+                    .into_iter()
+                    .collect()
                 ))
             )),
-            parse_object("{\"a\": \"x\", \"b\": true}")
+            parse_object::<Complete, Lenient>("{\"a\": \"x\", \"b\": true}")
         );
 
         assert_eq!(
-            parse_object("something"),
-            Err(nom::Err::Error(make_error(
-                "something",
-                nom::error::ErrorKind::Tag
-            )))
+            parse_object::<Complete, Lenient>("something"),
+            Err(nom::Err::Error(JsonError {
+                input: "something",
+                kind: JsonErrorKind::ExpectedToken('{'),
+                context: Some("object"),
+            }))
+        );
+    }
+
+    #[test]
+    fn object_key_must_be_a_string() {
+        assert_eq!(
+            parse_object_key::<Complete, Lenient>("1: true}").unwrap_err(),
+            nom::Err::Error(JsonError::new("1: true}", JsonErrorKind::ExpectedObjectKey))
+        );
+    }
+
+    #[test]
+    fn streaming_mode_asks_for_more_input_on_truncated_data() {
+        // A string cut off before its closing quote: complete mode treats the
+        // missing `"` as a hard error, streaming mode asks for more bytes.
+        assert_eq!(
+            parse_string::<Complete, Lenient>("\"abc"),
+            Err(nom::Err::Error(JsonError {
+                input: "",
+                kind: JsonErrorKind::ExpectedToken('"'),
+                context: Some("string"),
+            }))
+        );
+        assert!(matches!(
+            parse_streaming("\"abc"),
+            Err(nom::Err::Incomplete(_))
+        ));
+
+        // A string whose closing quote is in the buffer parses the same way
+        // under both modes.
+        assert_eq!(
+            parse_streaming("\"abc\""),
+            Ok(("", JsonNode::String("abc".into())))
         );
     }
+
+    #[test]
+    fn parse_skips_surrounding_whitespace_and_rejects_trailing_data() {
+        assert_eq!(parse("true"), Ok(JsonNode::Boolean(true)));
+        assert_eq!(parse("  \n true \t"), Ok(JsonNode::Boolean(true)));
+
+        assert_eq!(
+            parse("true true"),
+            Err(JsonError {
+                input: "true",
+                kind: JsonErrorKind::UnexpectedToken,
+                context: Some("trailing data after the end of the document"),
+            })
+        );
+    }
+
+    #[test]
+    fn strict_mode_rejects_json_extensions_lenient_mode_accepts() {
+        assert_eq!(parse(".14"), Ok(JsonNode::Number(0.14)));
+        assert_eq!(
+            parse_strict(".14"),
+            Err(JsonError {
+                input: ".14",
+                kind: JsonErrorKind::UnexpectedToken,
+                context: None,
+            })
+        );
+
+        assert_eq!(parse("+1"), Ok(JsonNode::Number(1.0)));
+        assert_eq!(
+            parse_strict("+1"),
+            Err(JsonError {
+                input: "+1",
+                kind: JsonErrorKind::UnexpectedToken,
+                context: None,
+            })
+        );
+
+        // A sign doesn't excuse a leading dot: `-.5` is just as non-conformant as `.5`.
+        assert_eq!(parse("-.5"), Ok(JsonNode::Number(-0.5)));
+        assert_eq!(
+            parse_strict("-.5"),
+            Err(JsonError {
+                input: "-.5",
+                kind: JsonErrorKind::UnexpectedToken,
+                context: None,
+            })
+        );
+
+        let control_char_in_string = "\"a\u{0007}b\"";
+        assert_eq!(
+            parse(control_char_in_string),
+            Ok(JsonNode::String("a\u{0007}b".into()))
+        );
+        assert!(parse_strict(control_char_in_string).is_err());
+
+        // U+007F (DEL) and U+0085 aren't ASCII control characters under RFC
+        // 8259's definition, unlike `char::is_control`, which flags both.
+        let non_ascii_control_chars_in_string = "\"a\u{7f}b\u{85}c\"";
+        assert!(parse_strict(non_ascii_control_chars_in_string).is_ok());
+
+        assert!(parse_strict("[1, 2]").is_ok());
+        assert!(parse_strict("[1, 2,]").is_err());
+        assert!(parse("[1, 2,]").is_err());
+
+        // An exponent large enough to overflow to infinity is still valid
+        // JSON number syntax, but RFC 8259 numbers must represent a finite
+        // value.
+        assert_eq!(parse("1e999"), Ok(JsonNode::Number(f64::INFINITY)));
+        assert_eq!(
+            parse_strict("1e999"),
+            Err(JsonError {
+                input: "1e999",
+                kind: JsonErrorKind::UnexpectedToken,
+                context: None,
+            })
+        );
+    }
+
+    #[test]
+    fn can_serialize_compact() {
+        let node = JsonNode::Object(Box::new(
+            vec![
+                ("a".into(), JsonNode::String("x\"y".into())),
+                (
+                    "b".into(),
+                    JsonNode::Array(Box::new(vec![JsonNode::Number(1.0), JsonNode::Null])),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        ));
+
+        assert_eq!(
+            node.to_json_string(),
+            r#"{"a":"x\"y","b":[1,null]}"#.to_string()
+        );
+    }
+
+    #[test]
+    fn can_serialize_pretty() {
+        let node = JsonNode::Object(Box::new(
+            vec![("a".into(), JsonNode::Array(Box::new(vec![JsonNode::Boolean(true)])))]
+                .into_iter()
+                .collect(),
+        ));
+
+        assert_eq!(
+            node.to_json_string_pretty(2),
+            "{\n  \"a\": [\n    true\n  ]\n}".to_string()
+        );
+    }
+
+    #[test]
+    fn round_trips_through_parse_and_serialize() {
+        let node = JsonNode::Object(Box::new(
+            vec![
+                ("name".into(), JsonNode::String("a\n\"b\"".into())),
+                ("age".into(), JsonNode::Number(42.5)),
+                ("tags".into(), JsonNode::Array(Box::new(vec![
+                    JsonNode::String("x".into()),
+                    JsonNode::Null,
+                    JsonNode::Boolean(false),
+                ]))),
+            ]
+            .into_iter()
+            .collect(),
+        ));
+
+        let serialized = node.to_json_string();
+        let (rest, parsed) = parse_json::<Complete, Lenient>(&serialized).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(parsed, node);
+    }
+
+    #[test]
+    fn serializes_non_finite_numbers_as_null() {
+        // NaN and +/-Infinity have no JSON representation, and serialization
+        // is infallible, so they come out as `null` instead of invalid JSON
+        // like `NaN` or `inf`.
+        assert_eq!(JsonNode::Number(f64::NAN).to_json_string(), "null");
+        assert_eq!(JsonNode::Number(f64::INFINITY).to_json_string(), "null");
+        assert_eq!(JsonNode::Number(f64::NEG_INFINITY).to_json_string(), "null");
+    }
+
+    #[test]
+    fn can_use_typed_accessors() {
+        let (_, node) = parse_json::<Complete, Lenient>(r#"{"a": "x", "b": 1.5, "c": true, "d": [1, 2], "e": null}"#)
+            .unwrap();
+
+        assert_eq!(node.get("a").and_then(JsonNode::as_str), Some("x"));
+        assert_eq!(node.get("b").and_then(JsonNode::as_f64), Some(1.5));
+        assert_eq!(node.get("c").and_then(JsonNode::as_bool), Some(true));
+        assert_eq!(
+            node.get("d")
+                .and_then(JsonNode::as_array)
+                .and_then(|items| items.first())
+                .and_then(JsonNode::as_f64),
+            Some(1.0)
+        );
+        assert!(node.get("e").unwrap().is_null());
+        assert_eq!(node.get("missing"), None);
+
+        let array = node.get("d").unwrap();
+        assert_eq!(array.get_index(1).and_then(JsonNode::as_f64), Some(2.0));
+        assert_eq!(array.get_index(5), None);
+
+        assert!(node.as_object().is_some());
+        assert!(node.as_array().is_none());
+    }
+
+    #[test]
+    fn can_convert_into_owned_values() {
+        assert_eq!(
+            String::try_from(JsonNode::String("x".into())),
+            Ok("x".to_string())
+        );
+        assert_eq!(f64::try_from(JsonNode::Number(1.5)), Ok(1.5));
+        assert_eq!(bool::try_from(JsonNode::Boolean(true)), Ok(true));
+        assert_eq!(
+            Vec::try_from(JsonNode::Array(Box::new(vec![JsonNode::Null]))),
+            Ok(vec![JsonNode::Null])
+        );
+
+        assert_eq!(
+            f64::try_from(JsonNode::Boolean(true)),
+            Err(JsonNode::Boolean(true))
+        );
+
+        let node: JsonNode = "hello".into();
+        assert_eq!(node, JsonNode::String("hello".into()));
+
+        let node: JsonNode = 3.0.into();
+        assert_eq!(node, JsonNode::Number(3.0));
+    }
 }